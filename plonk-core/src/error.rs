@@ -0,0 +1,48 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Errors related to the fan-in-3 Plonk gadgets and lookup argument.
+
+use core::fmt;
+
+/// Represents an error in the lookup subsystem, either in the construction
+/// of a lookup/witness table or in the verification of a query against one.
+#[derive(Debug, Eq, PartialEq)]
+pub enum Error {
+    /// This error occurs when a lookup query (the compressed wire values of
+    /// a witness row) cannot be found in the corresponding lookup table.
+    LookupQueryNotFound,
+
+    /// This error occurs when a lookup query supplies a different number of
+    /// input values than the table's arity expects, e.g. a caller juggling
+    /// several differently-shaped `LookupTable`s passes one table's query
+    /// to another. Checked at every query so it can never be mistaken for
+    /// a spurious [`LookupQueryNotFound`](Self::LookupQueryNotFound).
+    LookupArityMismatch {
+        /// The number of input values the table's arity requires.
+        expected: usize,
+        /// The number of input values the query actually supplied.
+        got: usize,
+    },
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::LookupQueryNotFound => {
+                write!(f, "the queried value does not exist in the lookup table")
+            }
+            Self::LookupArityMismatch { expected, got } => write!(
+                f,
+                "lookup query supplied {} input value(s), but the table expects {}",
+                got, expected
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for Error {}
@@ -0,0 +1,27 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! Fan-in-3 Plonk circuit library, including the lookup argument gadgets.
+
+pub mod error;
+pub mod lookup;
+
+/// Generates one `#[test]` per function in `$test_set`, each running that
+/// function against the field `$field`. Functions listed in
+/// `$test_pairing` are accepted but no test is generated for them.
+#[macro_export]
+macro_rules! batch_field_test {
+    ([$($test_set:ident),*], [$($test_pairing:ident),*] => $field:ty) => {
+        paste::item! {
+            $(
+                #[test]
+                fn [<$test_set _ $field>]() {
+                    $test_set::<$field>();
+                }
+            )*
+        }
+    };
+}
@@ -0,0 +1,287 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! An alternative to the plookup-style multiset-equality argument in
+//! [`LookupTable`]/[`WitnessTable`]: the log-derivative lookup argument
+//! (`LogUp`), following o1-labs' Logup helpers and halo2's
+//! `logup_skip_inv` lookup implementation. Unlike plookup, LogUp does not
+//! sort the concatenated witness+table multiset, and repeated queries
+//! into the same table row are folded into a single multiplicity rather
+//! than being duplicated, so it scales better when a table is queried
+//! many times.
+
+use crate::error::Error;
+use crate::lookup::{LookupTable4, TableTag, WitnessTable4};
+use ark_ff::Field;
+use std::collections::HashMap;
+
+/// Compresses every row `[t_1, t_2, t_3, t_4]` of a [`LookupTable4`] into
+/// a single field element `t_j = t_1 + beta*t_2 + beta^2*t_3 +
+/// beta^3*t_4` under a random challenge `beta`, for use in the `LogUp`
+/// identity.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LogupTable<F>
+where
+    F: Field,
+{
+    /// The compressed table rows, in the order they appear in the
+    /// [`LookupTable4`] they were built from.
+    pub compressed: Vec<F>,
+}
+
+impl<F> LogupTable<F>
+where
+    F: Field,
+{
+    /// Compresses every row of `table` under the folding challenge
+    /// `beta`. Tagged rows additionally fold in their [`TableTag`], so a
+    /// witness row can only match a compressed table entry from the
+    /// sub-table it was queried against.
+    pub fn compress(table: &LookupTable4<F>, beta: F) -> Self {
+        Self {
+            compressed: table
+                .rows
+                .iter()
+                .zip(table.tags.iter())
+                .map(|(row, tag)| compress_row(row, *tag, beta))
+                .collect(),
+        }
+    }
+}
+
+/// The prover-side witness for the `LogUp` argument: the compressed
+/// witness rows `f_i` and, for every table row `t_j`, the multiplicity
+/// `m_j` counting how many `f_i` are equal to it.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct LogupWitness<F>
+where
+    F: Field,
+{
+    /// The compressed witness rows `f_i = f_1 + beta*f_2 + beta^2*f_3 +
+    /// beta^3*f_4`.
+    pub compressed: Vec<F>,
+
+    /// `multiplicities[j]` is the number of compressed witness rows equal
+    /// to `table.compressed[j]`, aligned with `table.compressed`.
+    pub multiplicities: Vec<F>,
+}
+
+impl<F> LogupWitness<F>
+where
+    F: Field,
+{
+    /// Compresses `witness` under `beta` and counts, for every row of
+    /// `table`, how many compressed witness rows match it.
+    ///
+    /// Every witness row must be present in `table`: if one is missing,
+    /// the multiplicity counting pass has nowhere to record it, and the
+    /// final `LogUp` sum can never be made to vanish, so this returns
+    /// [`Error::LookupQueryNotFound`] rather than silently dropping the
+    /// row.
+    pub fn new(
+        witness: &WitnessTable4<F>,
+        table: &LogupTable<F>,
+        beta: F,
+    ) -> Result<Self, Error> {
+        let compressed: Vec<F> = (0..witness.columns[0].len())
+            .map(|i| {
+                compress_row(
+                    &[
+                        witness.columns[0].0[i],
+                        witness.columns[1].0[i],
+                        witness.columns[2].0[i],
+                        witness.columns[3].0[i],
+                    ],
+                    witness.tags[i],
+                    beta,
+                )
+            })
+            .collect();
+
+        let mut index_of_row = HashMap::with_capacity(table.compressed.len());
+        for (j, t) in table.compressed.iter().enumerate() {
+            index_of_row.entry(*t).or_insert(j);
+        }
+
+        let mut counts = vec![0u64; table.compressed.len()];
+        for f in &compressed {
+            let j = *index_of_row.get(f).ok_or(Error::LookupQueryNotFound)?;
+            counts[j] += 1;
+        }
+
+        Ok(Self {
+            compressed,
+            multiplicities: counts.into_iter().map(F::from).collect(),
+        })
+    }
+
+    /// Computes the running sum column for the log-derivative identity
+    ///
+    /// `sum_i 1/(alpha + f_i) - sum_j m_j/(alpha + t_j) == 0`
+    ///
+    /// under the challenge `alpha`. The last entry of the returned column
+    /// is the value that must be checked to be zero.
+    ///
+    /// Table rows with a zero multiplicity are `skip_inv`'d: their
+    /// inverse is never computed, since a zero numerator makes the term
+    /// vanish regardless, mirroring halo2's `logup_skip_inv` lookup
+    /// argument.
+    pub fn running_sum(&self, table: &LogupTable<F>, alpha: F) -> Vec<F> {
+        let f_denominators: Vec<F> =
+            self.compressed.iter().map(|f| alpha + f).collect();
+        let f_inverses = batch_invert(&f_denominators);
+
+        let t_terms: Vec<F> = table
+            .compressed
+            .iter()
+            .zip(self.multiplicities.iter())
+            .map(|(t, &m)| {
+                if m.is_zero() {
+                    F::zero()
+                } else {
+                    m * (alpha + t)
+                        .inverse()
+                        .expect("alpha was sampled after the table was fixed")
+                }
+            })
+            .collect();
+
+        let mut running_sum = Vec::with_capacity(f_inverses.len() + t_terms.len());
+        let mut acc = F::zero();
+        for term in &f_inverses {
+            acc += term;
+            running_sum.push(acc);
+        }
+        for term in &t_terms {
+            acc -= term;
+            running_sum.push(acc);
+        }
+        running_sum
+    }
+}
+
+/// Folds a lookup row `[r_1, r_2, r_3, r_4]` into a single field element
+/// `r_1 + beta*r_2 + beta^2*r_3 + beta^3*r_4`, additionally folding in
+/// `tag` as a fifth term (`beta^4 * (tag + 1)`) when the row came from a
+/// tagged sub-table, so rows from different sub-tables, or a tagged and
+/// an untagged row, never compress to the same value even when their
+/// four wire values coincide.
+fn compress_row<F: Field>(row: &[F; 4], tag: Option<TableTag>, beta: F) -> F {
+    let beta_sq = beta.square();
+    let folded = row[0] + beta * row[1] + beta_sq * row[2] + beta_sq * beta * row[3];
+    match tag {
+        Some(TableTag(idx)) => folded + beta_sq * beta_sq * F::from(idx as u64 + 1),
+        None => folded,
+    }
+}
+
+/// Inverts every element of `values` using a single field inversion (the
+/// standard Montgomery batch-inversion trick).
+///
+/// # Panics
+///
+/// Panics if any element of `values` is zero.
+fn batch_invert<F: Field>(values: &[F]) -> Vec<F> {
+    let mut prefix = Vec::with_capacity(values.len());
+    let mut acc = F::one();
+    for v in values {
+        prefix.push(acc);
+        acc *= v;
+    }
+    let mut acc_inv = acc.inverse().expect("batch_invert: zero denominator");
+
+    let mut result = vec![F::zero(); values.len()];
+    for i in (0..values.len()).rev() {
+        result[i] = prefix[i] * acc_inv;
+        acc_inv *= values[i];
+    }
+    result
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_field_test;
+    use ark_bls12_377::Fr as bls12_377_scalar_field;
+    use ark_bls12_381::Fr as bls12_381_scalar_field;
+
+    fn test_logup_balances_for_table_witness<F>()
+    where
+        F: Field,
+    {
+        let lookup_table = LookupTable4::<F>::xor_table(0, 3);
+        let beta = F::from(7u64);
+        let logup_table = LogupTable::compress(&lookup_table, beta);
+
+        let mut witness = WitnessTable4::<F>::new();
+        witness
+            .value_from_table(
+                &lookup_table,
+                &[F::from(2u64), F::from(5u64), -F::one()],
+            )
+            .unwrap();
+        witness
+            .value_from_table(
+                &lookup_table,
+                &[F::from(2u64), F::from(5u64), -F::one()],
+            )
+            .unwrap();
+
+        let logup_witness =
+            LogupWitness::new(&witness, &logup_table, beta).unwrap();
+        // Both witness rows are the same query, so it should be counted
+        // with multiplicity two and nowhere else.
+        assert_eq!(
+            logup_witness.multiplicities.iter().filter(|m| !m.is_zero()).count(),
+            1
+        );
+        assert!(logup_witness
+            .multiplicities
+            .iter()
+            .any(|&m| m == F::from(2u64)));
+
+        let alpha = F::from(11u64);
+        let running_sum = logup_witness.running_sum(&logup_table, alpha);
+        assert_eq!(*running_sum.last().unwrap(), F::zero());
+    }
+
+    fn test_logup_rejects_witness_row_missing_from_table<F>()
+    where
+        F: Field,
+    {
+        let lookup_table = LookupTable4::<F>::xor_table(0, 3);
+        let beta = F::from(7u64);
+        let logup_table = LogupTable::compress(&lookup_table, beta);
+
+        // Bypass `value_from_table` to build a witness row whose query
+        // does not exist in the table.
+        let mut witness = WitnessTable4::<F>::new();
+        witness.from_wire_values(&[
+            F::from(25u64),
+            F::from(5u64),
+            F::from(28u64),
+            -F::one(),
+        ]);
+
+        assert!(LogupWitness::new(&witness, &logup_table, beta).is_err());
+    }
+
+    batch_field_test!(
+        [
+            test_logup_balances_for_table_witness,
+            test_logup_rejects_witness_row_missing_from_table
+        ],
+        [] => bls12_381_scalar_field
+    );
+
+    batch_field_test!(
+        [
+            test_logup_balances_for_table_witness,
+            test_logup_rejects_witness_row_missing_from_table
+        ],
+        [] => bls12_377_scalar_field
+    );
+}
@@ -0,0 +1,595 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use crate::error::Error;
+use ark_ff::Field;
+
+/// A distinct tag identifying one sub-table registered with a
+/// [`LookupTable`], modeled on halo2's dynamic tables. Checking a row's tag
+/// alongside its input columns (see
+/// [`WitnessTable::value_from_tagged_table`]) guarantees the query can only
+/// be satisfied by rows of the operation it was registered for, even if
+/// another sub-table's rows happen to carry the same input values. Here
+/// the tag is a plain Rust-side field next to each row (see
+/// [`LookupTable::tags`]); [`LogupTable`] is the layer that actually folds
+/// a row's tag into a single field element alongside its other columns.
+///
+/// [`WitnessTable::value_from_tagged_table`]: crate::lookup::WitnessTable::value_from_tagged_table
+/// [`LogupTable`]: crate::lookup::LogupTable
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct TableTag(pub usize);
+
+/// `LookupTable` holds the rows of a (possibly multi-operation) lookup
+/// table of arity `W`: every row has `W` columns, one of which —
+/// `output_index` — holds the value looked up by the rest. This
+/// covers shapes beyond the original hard-wired "three inputs, one
+/// output": a 2-in/1-out range-composition table is `LookupTable<F, 3>`,
+/// a 4-in table for a wide bitwise op is `LookupTable<F, 5>`, and a
+/// 1-in/1-out range check is `LookupTable<F, 2>`. [`LookupTable4`] keeps
+/// the original four-column shape available under its own name.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LookupTable<F, const W: usize>
+where
+    F: Field,
+{
+    /// The rows of the table.
+    pub rows: Vec<[F; W]>,
+
+    /// `tags[i]` is the [`TableTag`] that `rows[i]` was inserted under, or
+    /// `None` if it was inserted through an untagged helper. Kept as a
+    /// plain side vector checked by [`lookup_tagged`](Self::lookup_tagged),
+    /// rather than a value folded into `rows[i]` itself — [`LogupTable`]
+    /// is the layer that folds a row's tag into its compressed value.
+    ///
+    /// [`LogupTable`]: crate::lookup::LogupTable
+    pub tags: Vec<Option<TableTag>>,
+
+    /// Which of the `W` columns holds the output; the rest, in column
+    /// order, are the inputs [`lookup`](Self::lookup) takes.
+    output_index: usize,
+
+    /// The friendly names passed to [`register`](Self::register), indexed
+    /// by `TableTag.0`.
+    names: Vec<String>,
+}
+
+/// The original four-column lookup table shape: three input columns
+/// (`left`, `right`, `fourth`) and one output column, laid out as
+/// `[left, right, output, fourth]` by [`new`](LookupTable::new). Kept
+/// so arity-4 call sites can keep naming the type they already used
+/// before [`LookupTable`] grew a const generic arity.
+pub type LookupTable4<F> = LookupTable<F, 4>;
+
+impl<F, const W: usize> LookupTable<F, W>
+where
+    F: Field,
+{
+    /// Creates an empty `LookupTable` whose output is column
+    /// `output_index`.
+    pub fn with_output_index(output_index: usize) -> Self {
+        assert!(
+            output_index < W,
+            "output_index must name one of the table's {} columns",
+            W
+        );
+        Self {
+            rows: Vec::new(),
+            tags: Vec::new(),
+            output_index,
+            names: Vec::new(),
+        }
+    }
+
+    /// The column holding the output that the other `W - 1` columns
+    /// look up.
+    pub fn output_index(&self) -> usize {
+        self.output_index
+    }
+
+    /// Registers a new sub-table under `name`, returning the distinct
+    /// [`TableTag`] future inserts into it should be tagged with.
+    pub fn register(&mut self, name: &str) -> TableTag {
+        let tag = TableTag(self.names.len());
+        self.names.push(name.to_string());
+        tag
+    }
+
+    /// Appends a single row, keeping `rows` and `tags` aligned.
+    fn push_row(&mut self, row: [F; W], tag: Option<TableTag>) {
+        self.rows.push(row);
+        self.tags.push(tag);
+    }
+
+    /// Looks up the output value of the row whose input columns match
+    /// `inputs`, returning an error if no such row exists. `inputs` must
+    /// hold exactly `W - 1` values, one for every column other than
+    /// [`output_index`](Self::output_index), in column order. Matches
+    /// rows regardless of their tag; use
+    /// [`lookup_tagged`](Self::lookup_tagged) to additionally require a
+    /// specific [`TableTag`].
+    pub fn lookup(&self, inputs: &[F]) -> Result<F, Error> {
+        Self::check_arity(inputs)?;
+        self.rows
+            .iter()
+            .find(|row| self.row_matches_inputs(row, inputs))
+            .map(|row| row[self.output_index])
+            .ok_or(Error::LookupQueryNotFound)
+    }
+
+    /// Like [`lookup`](Self::lookup), but additionally requires the
+    /// matching row's entry in [`tags`](Self::tags) to equal `tag`, so a
+    /// query can never be satisfied by a different sub-table's row.
+    pub fn lookup_tagged(&self, tag: TableTag, inputs: &[F]) -> Result<F, Error> {
+        Self::check_arity(inputs)?;
+        self.rows
+            .iter()
+            .zip(self.tags.iter())
+            .find(|(row, row_tag)| {
+                **row_tag == Some(tag) && self.row_matches_inputs(row, inputs)
+            })
+            .map(|(row, _)| row[self.output_index])
+            .ok_or(Error::LookupQueryNotFound)
+    }
+
+    /// Returns an error unless `inputs` holds exactly `W - 1` values, one
+    /// per input column. Enforced on every query, not just in debug
+    /// builds: a release-mode caller that mismatches arity (e.g. juggling
+    /// several differently-shaped `LookupTable`s) must get a hard error
+    /// rather than have `row_matches_inputs`'s `zip` silently ignore the
+    /// extra or missing values and spuriously match.
+    fn check_arity(inputs: &[F]) -> Result<(), Error> {
+        if inputs.len() == W - 1 {
+            Ok(())
+        } else {
+            Err(Error::LookupArityMismatch {
+                expected: W - 1,
+                got: inputs.len(),
+            })
+        }
+    }
+
+    /// Whether `row`'s input columns (every column other than
+    /// [`output_index`](Self::output_index), in column order) equal
+    /// `inputs`. Callers must have already checked `inputs` holds exactly
+    /// `W - 1` values via [`check_arity`](Self::check_arity).
+    fn row_matches_inputs(&self, row: &[F; W], inputs: &[F]) -> bool {
+        debug_assert_eq!(inputs.len(), W - 1);
+        row.iter()
+            .enumerate()
+            .filter(|(j, _)| *j != self.output_index)
+            .map(|(_, cell)| cell)
+            .zip(inputs.iter())
+            .all(|(cell, input)| cell == input)
+    }
+}
+
+impl<F> LookupTable4<F>
+where
+    F: Field,
+{
+    /// Creates an empty four-column `LookupTable` with the output in the
+    /// third column, matching the original `[left, right, output,
+    /// fourth]` row shape.
+    pub fn new() -> Self {
+        Self::with_output_index(2)
+    }
+
+    /// Builds a standalone XOR table for every `(a, b)` pair with
+    /// `lower_bound <= a, b < 2^bit_num`.
+    pub fn xor_table(lower_bound: u64, bit_num: u64) -> Self {
+        let mut table = Self::new();
+        table.insert_multi_xor(lower_bound, bit_num);
+        table
+    }
+
+    /// Appends `(a, b, a ^ b, -1)` for every `(a, b)` pair with
+    /// `lower_bound <= a, b < 2^bit_num`, tagging the rows with the XOR
+    /// selector `-1`.
+    pub fn insert_multi_xor(&mut self, lower_bound: u64, bit_num: u64) {
+        self.insert_multi_xor_rows(None, lower_bound, bit_num)
+    }
+
+    /// Like [`insert_multi_xor`](Self::insert_multi_xor), but records
+    /// every row under `tag` so it can only be matched through
+    /// [`lookup_tagged`](LookupTable::lookup_tagged) with the same tag.
+    pub fn insert_multi_xor_tagged(
+        &mut self,
+        tag: TableTag,
+        lower_bound: u64,
+        bit_num: u64,
+    ) {
+        self.insert_multi_xor_rows(Some(tag), lower_bound, bit_num)
+    }
+
+    fn insert_multi_xor_rows(
+        &mut self,
+        tag: Option<TableTag>,
+        lower_bound: u64,
+        bit_num: u64,
+    ) {
+        let upper_bound = 1u64 << bit_num;
+        for a in lower_bound..upper_bound {
+            for b in lower_bound..upper_bound {
+                self.push_row(
+                    [F::from(a), F::from(b), F::from(a ^ b), -F::one()],
+                    tag,
+                );
+            }
+        }
+    }
+
+    /// Appends `(a, b, a + b, 0)` for every `(a, b)` pair with
+    /// `lower_bound <= a, b < 2^bit_num`, tagging the rows with the
+    /// addition selector `0`.
+    pub fn insert_multi_add(&mut self, lower_bound: u64, bit_num: u64) {
+        self.insert_multi_add_rows(None, lower_bound, bit_num)
+    }
+
+    /// Like [`insert_multi_add`](Self::insert_multi_add), but records
+    /// every row under `tag` so it can only be matched through
+    /// [`lookup_tagged`](LookupTable::lookup_tagged) with the same tag.
+    pub fn insert_multi_add_tagged(
+        &mut self,
+        tag: TableTag,
+        lower_bound: u64,
+        bit_num: u64,
+    ) {
+        self.insert_multi_add_rows(Some(tag), lower_bound, bit_num)
+    }
+
+    fn insert_multi_add_rows(
+        &mut self,
+        tag: Option<TableTag>,
+        lower_bound: u64,
+        bit_num: u64,
+    ) {
+        let upper_bound = 1u64 << bit_num;
+        for a in lower_bound..upper_bound {
+            for b in lower_bound..upper_bound {
+                self.push_row(
+                    [F::from(a), F::from(b), F::from(a + b), F::zero()],
+                    tag,
+                );
+            }
+        }
+    }
+
+    /// Builds a standalone AND table for every `(a, b)` pair with
+    /// `lower_bound <= a, b < 2^bit_num`.
+    pub fn and_table(lower_bound: u64, bit_num: u64) -> Self {
+        let mut table = Self::new();
+        table.insert_multi_and(lower_bound, bit_num);
+        table
+    }
+
+    /// Appends `(a, b, a & b, 2)` for every `(a, b)` pair with
+    /// `lower_bound <= a, b < 2^bit_num`, tagging the rows with the AND
+    /// selector `2`.
+    pub fn insert_multi_and(&mut self, lower_bound: u64, bit_num: u64) {
+        self.insert_multi_and_rows(None, lower_bound, bit_num)
+    }
+
+    /// Like [`insert_multi_and`](Self::insert_multi_and), but records
+    /// every row under `tag` so it can only be matched through
+    /// [`lookup_tagged`](LookupTable::lookup_tagged) with the same tag.
+    pub fn insert_multi_and_tagged(
+        &mut self,
+        tag: TableTag,
+        lower_bound: u64,
+        bit_num: u64,
+    ) {
+        self.insert_multi_and_rows(Some(tag), lower_bound, bit_num)
+    }
+
+    fn insert_multi_and_rows(
+        &mut self,
+        tag: Option<TableTag>,
+        lower_bound: u64,
+        bit_num: u64,
+    ) {
+        let upper_bound = 1u64 << bit_num;
+        for a in lower_bound..upper_bound {
+            for b in lower_bound..upper_bound {
+                self.push_row(
+                    [F::from(a), F::from(b), F::from(a & b), F::from(2u64)],
+                    tag,
+                );
+            }
+        }
+    }
+
+    /// Builds a standalone OR table for every `(a, b)` pair with
+    /// `lower_bound <= a, b < 2^bit_num`.
+    pub fn or_table(lower_bound: u64, bit_num: u64) -> Self {
+        let mut table = Self::new();
+        table.insert_multi_or(lower_bound, bit_num);
+        table
+    }
+
+    /// Appends `(a, b, a | b, 3)` for every `(a, b)` pair with
+    /// `lower_bound <= a, b < 2^bit_num`, tagging the rows with the OR
+    /// selector `3`.
+    pub fn insert_multi_or(&mut self, lower_bound: u64, bit_num: u64) {
+        self.insert_multi_or_rows(None, lower_bound, bit_num)
+    }
+
+    /// Like [`insert_multi_or`](Self::insert_multi_or), but records every
+    /// row under `tag` so it can only be matched through
+    /// [`lookup_tagged`](LookupTable::lookup_tagged) with the same tag.
+    pub fn insert_multi_or_tagged(
+        &mut self,
+        tag: TableTag,
+        lower_bound: u64,
+        bit_num: u64,
+    ) {
+        self.insert_multi_or_rows(Some(tag), lower_bound, bit_num)
+    }
+
+    fn insert_multi_or_rows(
+        &mut self,
+        tag: Option<TableTag>,
+        lower_bound: u64,
+        bit_num: u64,
+    ) {
+        let upper_bound = 1u64 << bit_num;
+        for a in lower_bound..upper_bound {
+            for b in lower_bound..upper_bound {
+                self.push_row(
+                    [F::from(a), F::from(b), F::from(a | b), F::from(3u64)],
+                    tag,
+                );
+            }
+        }
+    }
+
+    /// Builds a standalone rotate table mapping every `a` with `0 <= a <
+    /// 2^width` to `a` rotated right by `amount` bits within `width`
+    /// bits. The unused right-hand column is always `0`; the rotation
+    /// amount doubles as the row selector, the same way the XOR/addition
+    /// selectors do.
+    pub fn rotate_table(width: u64, amount: u64) -> Self {
+        let mut table = Self::new();
+        table.insert_multi_rotate(width, amount);
+        table
+    }
+
+    /// Appends `(a, 0, rotate_right(a, amount, width), amount)` for every
+    /// `a` with `0 <= a < 2^width`.
+    pub fn insert_multi_rotate(&mut self, width: u64, amount: u64) {
+        self.insert_multi_rotate_rows(None, width, amount)
+    }
+
+    /// Like [`insert_multi_rotate`](Self::insert_multi_rotate), but
+    /// records every row under `tag` so it can only be matched through
+    /// [`lookup_tagged`](LookupTable::lookup_tagged) with the same tag.
+    /// Untagged rotate tables for different amounts can also share one
+    /// [`LookupTable`]: the amount sits in the selector column, which is
+    /// part of every query, so their rows never collide.
+    pub fn insert_multi_rotate_tagged(
+        &mut self,
+        tag: TableTag,
+        width: u64,
+        amount: u64,
+    ) {
+        self.insert_multi_rotate_rows(Some(tag), width, amount)
+    }
+
+    fn insert_multi_rotate_rows(
+        &mut self,
+        tag: Option<TableTag>,
+        width: u64,
+        amount: u64,
+    ) {
+        let upper_bound = 1u64 << width;
+        for a in 0..upper_bound {
+            let rotated = rotate_right(a, amount, width);
+            self.push_row(
+                [F::from(a), F::zero(), F::from(rotated), F::from(amount)],
+                tag,
+            );
+        }
+    }
+}
+
+impl<F> Default for LookupTable4<F>
+where
+    F: Field,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Rotates the bottom `width` bits of `value` right by `amount` bits,
+/// wrapping within `width` (e.g. `rotate_right(0b0001, 1, 4) == 0b1000`).
+pub(crate) fn rotate_right(value: u64, amount: u64, width: u64) -> u64 {
+    let amount = amount % width;
+    let mask = (1u64 << width) - 1;
+    if amount == 0 {
+        value & mask
+    } else {
+        ((value >> amount) | (value << (width - amount))) & mask
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::batch_field_test;
+    use ark_bls12_377::Fr as bls12_377_scalar_field;
+    use ark_bls12_381::Fr as bls12_381_scalar_field;
+
+    fn test_and_table_rows_and_lookup<F>()
+    where
+        F: Field,
+    {
+        let table = LookupTable4::<F>::and_table(0, 2);
+
+        // Every `(a, b)` pair with `0 <= a, b < 4` shows up exactly once,
+        // selector column `2`.
+        assert_eq!(table.rows.len(), 16);
+        assert!(table.rows.contains(&[
+            F::from(3u64),
+            F::from(1u64),
+            F::from(1u64),
+            F::from(2u64)
+        ]));
+        assert!(table.tags.iter().all(Option::is_none));
+
+        assert_eq!(
+            table.lookup(&[F::from(3u64), F::from(1u64), F::from(2u64)]),
+            Ok(F::from(1u64))
+        );
+        assert!(table
+            .lookup(&[F::from(3u64), F::from(1u64), F::from(3u64)])
+            .is_err());
+    }
+
+    fn test_or_table_rows_and_lookup<F>()
+    where
+        F: Field,
+    {
+        let table = LookupTable4::<F>::or_table(0, 2);
+
+        assert_eq!(table.rows.len(), 16);
+        assert!(table.rows.contains(&[
+            F::from(2u64),
+            F::from(1u64),
+            F::from(3u64),
+            F::from(3u64)
+        ]));
+        assert!(table.tags.iter().all(Option::is_none));
+
+        assert_eq!(
+            table.lookup(&[F::from(2u64), F::from(1u64), F::from(3u64)]),
+            Ok(F::from(3u64))
+        );
+        assert!(table
+            .lookup(&[F::from(2u64), F::from(1u64), F::from(2u64)])
+            .is_err());
+    }
+
+    fn test_and_or_tagged_cannot_cross_tables<F>()
+    where
+        F: Field,
+    {
+        // AND and OR inserted into the same table under their own tags:
+        // a query can only be satisfied by the operation it was tagged
+        // for, even though both sub-tables' selector columns (`2` and
+        // `3`) are part of the shared row shape.
+        let mut table = LookupTable4::<F>::new();
+        let and_tag = table.register("and");
+        let or_tag = table.register("or");
+        table.insert_multi_and_tagged(and_tag, 0, 2);
+        table.insert_multi_or_tagged(or_tag, 0, 2);
+
+        assert_eq!(
+            table.lookup_tagged(
+                and_tag,
+                &[F::from(3u64), F::from(1u64), F::from(2u64)]
+            ),
+            Ok(F::from(1u64))
+        );
+        assert_eq!(
+            table.lookup_tagged(
+                or_tag,
+                &[F::from(2u64), F::from(1u64), F::from(3u64)]
+            ),
+            Ok(F::from(3u64))
+        );
+        // The OR tag can't be used to resolve an AND row, even though an
+        // untagged lookup with the same inputs succeeds (it matches the
+        // AND row regardless of tag).
+        assert!(table
+            .lookup(&[F::from(3u64), F::from(1u64), F::from(2u64)])
+            .is_ok());
+        assert!(table
+            .lookup_tagged(
+                or_tag,
+                &[F::from(3u64), F::from(1u64), F::from(2u64)]
+            )
+            .is_err());
+    }
+
+    fn test_rotate_table_rows_and_lookup<F>()
+    where
+        F: Field,
+    {
+        let table = LookupTable4::<F>::rotate_table(4, 1);
+
+        // Every `a` with `0 <= a < 16` shows up once, unused right-hand
+        // column `0`, selector column holding the rotation amount.
+        assert_eq!(table.rows.len(), 16);
+        assert!(table.rows.contains(&[
+            F::from(1u64),
+            F::zero(),
+            F::from(8u64),
+            F::from(1u64)
+        ]));
+
+        assert_eq!(
+            table.lookup(&[F::from(1u64), F::zero(), F::from(1u64)]),
+            Ok(F::from(8u64))
+        );
+        // A right-hand column mismatch (not `0`) never matches.
+        assert!(table
+            .lookup(&[F::from(1u64), F::one(), F::from(1u64)])
+            .is_err());
+    }
+
+    fn test_untagged_rotate_tables_selector_does_not_collide<F>()
+    where
+        F: Field,
+    {
+        // Two untagged rotate sub-tables for different amounts, inserted
+        // into one table. The rotation amount is itself part of every
+        // row's query key (the fourth column), so the two amounts never
+        // collide even without distinct TableTags.
+        let mut table = LookupTable4::<F>::new();
+        table.insert_multi_rotate(4, 1);
+        table.insert_multi_rotate(4, 2);
+        assert_eq!(table.rows.len(), 32);
+
+        // a = 0b0001 rotated right by 1 is 0b1000, by 2 is 0b0100.
+        assert_eq!(
+            table.lookup(&[F::from(1u64), F::zero(), F::from(1u64)]),
+            Ok(F::from(8u64))
+        );
+        assert_eq!(
+            table.lookup(&[F::from(1u64), F::zero(), F::from(2u64)]),
+            Ok(F::from(4u64))
+        );
+        // Querying `a = 1` with a rotation amount neither table was
+        // built for finds nothing.
+        assert!(table
+            .lookup(&[F::from(1u64), F::zero(), F::from(3u64)])
+            .is_err());
+    }
+
+    // Bls12-381 tests
+    batch_field_test!(
+        [
+            test_and_table_rows_and_lookup,
+            test_or_table_rows_and_lookup,
+            test_and_or_tagged_cannot_cross_tables,
+            test_rotate_table_rows_and_lookup,
+            test_untagged_rotate_tables_selector_does_not_collide
+        ],
+        [] => bls12_381_scalar_field
+    );
+
+    // Bls12-377 tests
+    batch_field_test!(
+        [
+            test_and_table_rows_and_lookup,
+            test_or_table_rows_and_lookup,
+            test_and_or_tagged_cannot_cross_tables,
+            test_rotate_table_rows_and_lookup,
+            test_untagged_rotate_tables_selector_does_not_collide
+        ],
+        [] => bls12_377_scalar_field
+    );
+}
@@ -0,0 +1,20 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+//! The lookup gadgets needed to prove plookup-style and log-derivative
+//! lookup arguments.
+
+mod blake3;
+mod lookup_table;
+mod logup;
+mod multiset;
+mod witness_table;
+
+pub use blake3::{Blake3Tables, BLAKE3_ROTATIONS};
+pub use lookup_table::{LookupTable, LookupTable4, TableTag};
+pub use logup::{LogupTable, LogupWitness};
+pub use multiset::MultiSet;
+pub use witness_table::{LookupFailure, WitnessTable, WitnessTable4};
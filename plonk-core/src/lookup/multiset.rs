@@ -0,0 +1,50 @@
+// This Source Code Form is subject to the terms of the Mozilla Public
+// License, v. 2.0. If a copy of the MPL was not distributed with this
+// file, You can obtain one at http://mozilla.org/MPL/2.0/.
+//
+// Copyright (c) DUSK NETWORK. All rights reserved.
+
+use ark_ff::Field;
+
+/// `MultiSet` is a struct containing a vector of scalars, representing
+/// either the wire values of a witness column or the rows of a lookup
+/// table column.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct MultiSet<F>(pub Vec<F>)
+where
+    F: Field;
+
+impl<F> MultiSet<F>
+where
+    F: Field,
+{
+    /// Creates an empty `MultiSet`.
+    pub fn new() -> Self {
+        MultiSet(Vec::new())
+    }
+
+    /// Pushes `value` onto the end of the `MultiSet`.
+    pub fn push(&mut self, value: F) {
+        self.0.push(value)
+    }
+
+    /// Returns the number of elements in the `MultiSet`.
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    /// Returns `true` if the `MultiSet` contains no elements.
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Returns `true` if `value` is found in the `MultiSet`.
+    pub fn contains(&self, value: &F) -> bool {
+        self.0.contains(value)
+    }
+
+    /// Returns an iterator over the elements of the `MultiSet`.
+    pub fn iter(&self) -> impl Iterator<Item = &F> {
+        self.0.iter()
+    }
+}
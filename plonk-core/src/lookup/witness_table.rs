@@ -5,98 +5,192 @@
 // Copyright (c) DUSK NETWORK. All rights reserved.
 
 use crate::error::Error;
-use crate::lookup::{LookupTable, MultiSet};
+use crate::lookup::{LookupTable, MultiSet, TableTag};
 use ark_ff::Field;
 
 /// This witness table contains quieries
-/// to a lookup table for lookup gates
-/// This table is of arity 3.
-#[derive(Clone, Debug, Default, Eq, PartialEq)]
-pub struct WitnessTable<F>
+/// to a lookup table for lookup gates.
+/// This table has a configurable arity `W`, matching the
+/// [`LookupTable<F, W>`](LookupTable) it is queried against; [`WitnessTable4`]
+/// keeps the original four-column shape available under its own name.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct WitnessTable<F, const W: usize>
 where
     F: Field,
 {
-    /// This column represents the
-    /// first values inside the lookup
-    /// table. At gate checks, this
-    /// can be regarded as the first
-    /// wire
-    pub f_1: MultiSet<F>,
-
-    /// This column represents the
-    /// first values inside the lookup
-    /// table. At gate checks, this
-    /// can be regarded as the second
-    /// wire
-    pub f_2: MultiSet<F>,
-
-    /// This column represents the
-    /// first values inside the lookup
-    /// table. At gate checks, this
-    /// can be regarded as the third
-    /// wire
-    pub f_3: MultiSet<F>,
-
-    /// This column represents the
-    /// first values inside the lookup
-    /// table. At gate checks, this
-    /// can be regarded as the fourth
-    /// wire
-    pub f_4: MultiSet<F>,
+    /// `columns[i]` holds the values of the witness table's `i`-th wire.
+    /// At gate checks, this can be regarded as that wire's column.
+    pub columns: [MultiSet<F>; W],
+
+    /// `tags[i]` is the [`TableTag`] the row at index `i` was resolved
+    /// against, or `None` if it was pushed through an untagged method.
+    pub tags: Vec<Option<TableTag>>,
+}
+
+/// The original four-column witness table shape. Kept so arity-4 call
+/// sites can keep naming the type they already used before
+/// [`WitnessTable`] grew a const generic arity.
+pub type WitnessTable4<F> = WitnessTable<F, 4>;
+
+/// Describes one witness row that [`WitnessTable::verify_against`] found
+/// missing from its lookup table, so failures can be inspected together
+/// instead of the caller only ever learning about the first one.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LookupFailure<F, const W: usize>
+where
+    F: Field,
+{
+    /// The index of the offending row within the witness table's
+    /// columns.
+    pub row_index: usize,
+
+    /// The offending row's wire values, in column order.
+    pub row: [F; W],
 }
 
-impl<F> WitnessTable<F>
+impl<F, const W: usize> WitnessTable<F, W>
 where
     F: Field,
 {
-    /// Initialses empty witness table of arity 4
+    /// Initialises an empty witness table of arity `W`.
     pub fn new() -> Self {
-        Default::default()
+        Self {
+            columns: std::array::from_fn(|_| MultiSet::new()),
+            tags: Vec::new(),
+        }
     }
 
     /// This allows the witness table to be filled directly without
     /// taking any vaules, or the the results, from the lookup table.
     /// If the values do no exists in the lookup table, then the proof
     /// will fail when witness and preprocessed tables are concatenated.
-    pub fn from_wire_values(
-        &mut self,
-        left_wire_val: F,
-        right_wire_val: F,
-        output_wire_val: F,
-        fourth_wire_val: F,
-    ) {
-        self.f_1.push(left_wire_val);
-        self.f_2.push(right_wire_val);
-        self.f_3.push(output_wire_val);
-        self.f_4.push(fourth_wire_val);
+    /// `values` must hold exactly `W` values, one per column.
+    pub fn from_wire_values(&mut self, values: &[F]) {
+        assert_eq!(values.len(), W, "from_wire_values expects W values");
+        for (column, &value) in self.columns.iter_mut().zip(values.iter()) {
+            column.push(value);
+        }
+        self.tags.push(None);
     }
 
-    /// Attempts to look up a value from a lookup table. If successful, all four
-    /// elements are pushed to their respective multisets.
+    /// Attempts to look up a value from a lookup table. If successful,
+    /// all `W` elements are pushed to their respective multisets.
+    /// `inputs` must hold exactly `W - 1` values, one per input column
+    /// of `lookup_table`, in column order.
     pub fn value_from_table(
         &mut self,
-        lookup_table: &LookupTable<F>,
-        left_wire_val: F,
-        right_wire_val: F,
-        fourth_wire_val: F,
+        lookup_table: &LookupTable<F, W>,
+        inputs: &[F],
     ) -> Result<(), Error> {
-        let output_wire_val = lookup_table.lookup(
-            left_wire_val,
-            right_wire_val,
-            fourth_wire_val,
-        )?;
-        self.f_1.push(left_wire_val);
-        self.f_2.push(right_wire_val);
-        self.f_3.push(output_wire_val);
-        self.f_4.push(fourth_wire_val);
+        let output = lookup_table.lookup(inputs)?;
+        self.push_row(lookup_table.output_index(), inputs, output, None);
         Ok(())
     }
+
+    /// Like [`value_from_table`](Self::value_from_table), but resolves
+    /// the query against the sub-table registered under `tag`, via
+    /// [`LookupTable::lookup_tagged`]. Checking the tag alongside the
+    /// query guarantees it can only be satisfied by rows of the operation
+    /// `tag` was registered for, even if another sub-table happens to
+    /// share the same input values.
+    pub fn value_from_tagged_table(
+        &mut self,
+        lookup_table: &LookupTable<F, W>,
+        tag: TableTag,
+        inputs: &[F],
+    ) -> Result<(), Error> {
+        let output = lookup_table.lookup_tagged(tag, inputs)?;
+        self.push_row(lookup_table.output_index(), inputs, output, Some(tag));
+        Ok(())
+    }
+
+    /// Pushes one row built from `inputs` (in column order, skipping
+    /// `output_index`) and the resolved `output`.
+    fn push_row(
+        &mut self,
+        output_index: usize,
+        inputs: &[F],
+        output: F,
+        tag: Option<TableTag>,
+    ) {
+        let mut inputs = inputs.iter();
+        for (j, column) in self.columns.iter_mut().enumerate() {
+            let value = if j == output_index {
+                output
+            } else {
+                *inputs.next().expect("inputs has W - 1 values")
+            };
+            column.push(value);
+        }
+        self.tags.push(tag);
+    }
+
+    /// Walks every already-populated row and checks it against
+    /// `lookup_table`, recomputing each row's input-column query key and
+    /// collecting a [`LookupFailure`] for every row that turns out to be
+    /// missing, rather than bailing out on the first one the way
+    /// [`value_from_table`](Self::value_from_table) does at push time.
+    pub fn verify_against(
+        &self,
+        lookup_table: &LookupTable<F, W>,
+    ) -> Result<(), Vec<LookupFailure<F, W>>> {
+        self.verify_rows_against(lookup_table, 0..self.columns[0].len())
+    }
+
+    /// Like [`verify_against`](Self::verify_against), but only checks the
+    /// rows named by `row_ids`, for faster debugging of a subrange of a
+    /// large witness table.
+    pub fn verify_rows_against(
+        &self,
+        lookup_table: &LookupTable<F, W>,
+        row_ids: impl Iterator<Item = usize>,
+    ) -> Result<(), Vec<LookupFailure<F, W>>> {
+        let output_index = lookup_table.output_index();
+        let failures: Vec<LookupFailure<F, W>> = row_ids
+            .filter_map(|row_index| {
+                let row: [F; W] =
+                    std::array::from_fn(|j| self.columns[j].0[row_index]);
+                let inputs: Vec<F> = row
+                    .iter()
+                    .enumerate()
+                    .filter(|(j, _)| *j != output_index)
+                    .map(|(_, &v)| v)
+                    .collect();
+
+                let found = match self.tags[row_index] {
+                    Some(tag) => lookup_table.lookup_tagged(tag, &inputs),
+                    None => lookup_table.lookup(&inputs),
+                };
+
+                match found {
+                    Ok(output) if output == row[output_index] => None,
+                    _ => Some(LookupFailure { row_index, row }),
+                }
+            })
+            .collect();
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(failures)
+        }
+    }
 }
+
+impl<F, const W: usize> Default for WitnessTable<F, W>
+where
+    F: Field,
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
     use crate::batch_field_test;
-    use crate::lookup::LookupTable;
+    use crate::lookup::LookupTable4;
     use ark_bls12_377::Fr as bls12_377_scalar_field;
     use ark_bls12_381::Fr as bls12_381_scalar_field;
 
@@ -105,26 +199,22 @@ mod test {
         F: Field,
     {
         // Build lookup table
-        let lookup_table = LookupTable::<F>::xor_table(0, 3);
+        let lookup_table = LookupTable4::<F>::xor_table(0, 3);
 
         // Instantiate empty multisets of wire values in witness table
-        let mut f = WitnessTable::<F>::new();
+        let mut f = WitnessTable4::<F>::new();
         // Read values from lookup table and insert into witness table
         assert!(f
             .value_from_table(
                 &lookup_table,
-                F::from(2u64),
-                F::from(5u64),
-                -F::one()
+                &[F::from(2u64), F::from(5u64), -F::one()]
             )
             .is_ok());
         // Check that non existent elements cause a failure
         assert!(f
             .value_from_table(
                 &lookup_table,
-                F::from(25u64),
-                F::from(5u64),
-                -F::one()
+                &[F::from(25u64), F::from(5u64), -F::one()]
             )
             .is_err());
     }
@@ -134,7 +224,7 @@ mod test {
         F: Field,
     {
         // Build empty lookup tables
-        let mut lookup_table = LookupTable::<F>::new();
+        let mut lookup_table = LookupTable4::<F>::new();
 
         // Add a consecutive set of tables, with
         // XOR operationd and addition operations
@@ -142,24 +232,20 @@ mod test {
         lookup_table.insert_multi_add(2, 3);
 
         // Build empty witness table
-        let mut f = WitnessTable::<F>::new();
+        let mut f = WitnessTable4::<F>::new();
 
         // Check for output of wires within lookup table and
         // if they exist input them to the witness table
         assert!(f
             .value_from_table(
                 &lookup_table,
-                F::from(2u32),
-                F::from(3u32),
-                -F::one()
+                &[F::from(2u32), F::from(3u32), -F::one()]
             )
             .is_ok());
         assert!(f
             .value_from_table(
                 &lookup_table,
-                F::from(4u32),
-                F::from(6u32),
-                F::zero()
+                &[F::from(4u32), F::from(6u32), F::zero()]
             )
             .is_ok());
 
@@ -168,21 +254,198 @@ mod test {
         assert!(f
             .value_from_table(
                 &lookup_table,
-                F::from(22u32),
-                F::one(),
-                -F::one()
+                &[F::from(22u32), F::one(), -F::one()]
             )
             .is_err());
         assert!(f
-            .value_from_table(&lookup_table, F::zero(), F::one(), F::zero())
+            .value_from_table(
+                &lookup_table,
+                &[F::zero(), F::one(), F::zero()]
+            )
             .is_err());
     }
 
+    fn test_tagged_lookup_cannot_cross_tables<F>()
+    where
+        F: Field,
+    {
+        // Two sub-tables that happen to overlap on (left, right, fourth):
+        // a 4-bit XOR table and a 2-bit XOR table registered under its own
+        // tag, both holding the row (2, 3, _, -1).
+        let mut lookup_table = LookupTable4::<F>::new();
+        let xor_tag = lookup_table.register("xor");
+        let other_tag = lookup_table.register("other-xor");
+        lookup_table.insert_multi_xor_tagged(xor_tag, 0, 4);
+        lookup_table.insert_multi_xor_tagged(other_tag, 0, 2);
+
+        let mut f = WitnessTable4::<F>::new();
+        // Resolves against the table it was actually registered for.
+        assert!(f
+            .value_from_tagged_table(
+                &lookup_table,
+                xor_tag,
+                &[F::from(2u64), F::from(3u64), -F::one()]
+            )
+            .is_ok());
+
+        // (9, 3, _, -1) exists only in the `xor_tag` sub-table, so it can't
+        // be resolved through `other_tag`.
+        assert!(f
+            .value_from_tagged_table(
+                &lookup_table,
+                other_tag,
+                &[F::from(9u64), F::from(3u64), -F::one()]
+            )
+            .is_err());
+
+        // A tag that was never registered can never match, even though
+        // the untagged query would succeed.
+        let unregistered_tag = TableTag(lookup_table.register("unused").0 + 1);
+        assert!(f
+            .value_from_tagged_table(
+                &lookup_table,
+                unregistered_tag,
+                &[F::from(2u64), F::from(3u64), -F::one()]
+            )
+            .is_err());
+    }
+
+    fn test_verify_against_reports_every_failing_row<F>()
+    where
+        F: Field,
+    {
+        let lookup_table = LookupTable4::<F>::xor_table(0, 3);
+
+        let mut f = WitnessTable4::<F>::new();
+        // A valid row, pushed directly so a bad row can follow it without
+        // `value_from_table` rejecting it up front.
+        f.from_wire_values(&[
+            F::from(2u64),
+            F::from(5u64),
+            F::from(7u64),
+            -F::one(),
+        ]);
+        // Two rows that don't exist in the table.
+        f.from_wire_values(&[
+            F::from(25u64),
+            F::from(5u64),
+            F::from(28u64),
+            -F::one(),
+        ]);
+        f.from_wire_values(&[
+            F::from(1u64),
+            F::from(1u64),
+            F::from(0u64),
+            F::zero(),
+        ]);
+
+        let failures = f.verify_against(&lookup_table).unwrap_err();
+        assert_eq!(failures.len(), 2);
+        assert_eq!(failures[0].row_index, 1);
+        assert_eq!(failures[1].row_index, 2);
+    }
+
+    fn test_verify_rows_against_restricts_to_given_indices<F>()
+    where
+        F: Field,
+    {
+        let lookup_table = LookupTable4::<F>::xor_table(0, 3);
+
+        let mut f = WitnessTable4::<F>::new();
+        f.from_wire_values(&[
+            F::from(25u64),
+            F::from(5u64),
+            F::from(28u64),
+            -F::one(),
+        ]);
+        f.from_wire_values(&[
+            F::from(2u64),
+            F::from(5u64),
+            F::from(7u64),
+            -F::one(),
+        ]);
+
+        // Restricting the check to the valid row should find nothing.
+        assert!(f.verify_rows_against(&lookup_table, 1..2).is_ok());
+        // Checking everything still finds the bad row.
+        assert!(f.verify_against(&lookup_table).is_err());
+    }
+
+    fn test_non_arity_4_shapes<F>()
+    where
+        F: Field,
+    {
+        // A 1-in/1-out range check: the output column mirrors the input,
+        // and only in-range values are present.
+        let mut range_table = LookupTable::<F, 2>::with_output_index(1);
+        for v in 0..8u64 {
+            range_table.rows.push([F::from(v), F::from(v)]);
+            range_table.tags.push(None);
+        }
+
+        let mut in_range = WitnessTable::<F, 2>::new();
+        assert!(in_range
+            .value_from_table(&range_table, &[F::from(3u64)])
+            .is_ok());
+        assert!(in_range
+            .value_from_table(&range_table, &[F::from(8u64)])
+            .is_err());
+
+        // A 2-in/1-out range-composition table, with the output as the
+        // first column rather than the last.
+        let mut composition_table = LookupTable::<F, 3>::with_output_index(0);
+        for hi in 0..4u64 {
+            for lo in 0..4u64 {
+                composition_table.rows.push([
+                    F::from(hi * 4 + lo),
+                    F::from(hi),
+                    F::from(lo),
+                ]);
+                composition_table.tags.push(None);
+            }
+        }
+
+        let mut composed = WitnessTable::<F, 3>::new();
+        assert!(composed
+            .value_from_table(
+                &composition_table,
+                &[F::from(2u64), F::from(3u64)]
+            )
+            .is_ok());
+        assert_eq!(composed.columns[0].0[0], F::from(11u64));
+    }
+
+    fn test_mismatched_arity_query_is_rejected<F>()
+    where
+        F: Field,
+    {
+        // A query with more input values than the table's arity expects
+        // must be rejected outright, not silently truncated by `zip` into
+        // a spurious match.
+        let lookup_table = LookupTable4::<F>::xor_table(0, 3);
+        let mut f = WitnessTable4::<F>::new();
+        assert_eq!(
+            f.value_from_table(
+                &lookup_table,
+                &[F::from(2u64), F::from(5u64), F::from(9u64), F::from(1u64)],
+            ),
+            Err(crate::error::Error::LookupArityMismatch {
+                expected: 3,
+                got: 4,
+            })
+        );
+    }
+
     // Bls12-381 tests
     batch_field_test!(
         [
             test_lookup_fuctionality_1,
-            test_lookup_fuctionality_2
+            test_lookup_fuctionality_2,
+            test_tagged_lookup_cannot_cross_tables,
+            test_verify_against_reports_every_failing_row,
+            test_verify_rows_against_restricts_to_given_indices,
+            test_non_arity_4_shapes,
+            test_mismatched_arity_query_is_rejected
         ],
         [] => bls12_381_scalar_field
     );
@@ -191,7 +454,12 @@ mod test {
     batch_field_test!(
         [
             test_lookup_fuctionality_1,
-            test_lookup_fuctionality_2
+            test_lookup_fuctionality_2,
+            test_tagged_lookup_cannot_cross_tables,
+            test_verify_against_reports_every_failing_row,
+            test_verify_rows_against_restricts_to_given_indices,
+            test_non_arity_4_shapes,
+            test_mismatched_arity_query_is_rejected
         ],
         [] => bls12_377_scalar_field
     );